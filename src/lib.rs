@@ -1,31 +1,38 @@
-use petgraph::algo::toposort;
-use petgraph::graph::DiGraph;
+use petgraph::algo::{tarjan_scc, toposort};
+use petgraph::dot::{Config, Dot};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::{HashMap, HashSet};
 
-/// Produces proposed dates of birth and death such that the collected facts hold true
-///
-/// # Parameters
-/// Let P_1, P_2, ..., P_n be a set of n deceased people.
-/// If person P_a died before person P_b was born then `fact_form1` will contain `(a, b)`.
-/// If person P_a and P_b's life span overlapped at least partially then `fact_form2` will contain `(a, b)`.
-///
-/// # Return value
-/// If the facts are internally consistent, returns `Some((birth, death))` where
-/// `birth[i - 1]` is the proposed birth date and `death[i - 1]` is the proposed death date of person P_i.
-/// Returns `None` if the facts are not internally consistent.
-///
-/// # Complexity
-/// `O(m + n)` where `m = fact_form1.len() + fact_form2.len()`.
-pub fn proposed_dates(
+/// A node in the constraint graph built from the supplied facts.
+#[derive(Debug)]
+enum Label {
+    Birth(usize), // Birth(i) corresponds to label B(i)
+    Death(usize), // Death(i) corresponds to label D(i)
+}
+
+/// A single fact fed into `proposed_dates`, tagged with its origin so it can be
+/// reported back to the caller when the facts turn out to be inconsistent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fact {
+    /// The lifespan of P_i, i.e. the built-in `(B(i), D(i))` edge.
+    Lifespan(usize),
+    /// A `fact_form1` entry: P_a died before P_b was born.
+    DiedBeforeBorn(u32, u32),
+    /// A `fact_form2` entry: P_a and P_b's lifespans overlapped.
+    Overlap(u32, u32),
+}
+
+/// Builds the constraint graph for the given facts, along with a table mapping
+/// each edge back to the fact that produced it.
+fn build_graph(
     fact_form1: &[(u32, u32)],
     fact_form2: &[(u32, u32)],
     n: usize,
-) -> Option<(Vec<usize>, Vec<usize>)> {
-    enum Label {
-        Birth(usize), // Birth(i) corresponds to label B(i)
-        Death(usize), // Death(i) corresponds to label D(i)
-    }
-
+) -> (DiGraph<Label, ()>, HashMap<EdgeIndex, Fact>) {
     let mut g = DiGraph::new();
+    let mut edge_facts = HashMap::new();
 
     // construct g with nodes B(i) and D(i) for each P_i
     let birth_nodes: Vec<_> = (1..=n).map(|i| g.add_node(Label::Birth(i))).collect(); // birth_nodes[i - 1] is the node labeled B(i)
@@ -33,32 +40,61 @@ pub fn proposed_dates(
 
     // for each P_i insert (B(i), D(i))
     for i in 1..=n {
-        g.add_edge(birth_nodes[i - 1], death_nodes[i - 1], ());
+        let edge = g.add_edge(birth_nodes[i - 1], death_nodes[i - 1], ());
+        edge_facts.insert(edge, Fact::Lifespan(i));
     }
 
     // for each first fact form (a, b) add edge (D(a), B(b))
-    for (i, j) in fact_form1 {
-        g.add_edge(
+    for &(i, j) in fact_form1 {
+        let edge = g.add_edge(
             death_nodes[(i - 1) as usize],
             birth_nodes[(j - 1) as usize],
             (),
         );
+        edge_facts.insert(edge, Fact::DiedBeforeBorn(i, j));
     }
 
     // for each second fact form (a, b) add edges (B(a), D(b)), (B(b), D(a))
-    for (i, j) in fact_form2 {
-        g.add_edge(
+    for &(i, j) in fact_form2 {
+        let edge = g.add_edge(
             birth_nodes[(i - 1) as usize],
             death_nodes[(j - 1) as usize],
             (),
         );
-        g.add_edge(
+        edge_facts.insert(edge, Fact::Overlap(i, j));
+
+        let edge = g.add_edge(
             birth_nodes[(j - 1) as usize],
             death_nodes[(i - 1) as usize],
             (),
         );
+        edge_facts.insert(edge, Fact::Overlap(i, j));
     }
 
+    (g, edge_facts)
+}
+
+/// Produces proposed dates of birth and death such that the collected facts hold true
+///
+/// # Parameters
+/// Let P_1, P_2, ..., P_n be a set of n deceased people.
+/// If person P_a died before person P_b was born then `fact_form1` will contain `(a, b)`.
+/// If person P_a and P_b's life span overlapped at least partially then `fact_form2` will contain `(a, b)`.
+///
+/// # Return value
+/// If the facts are internally consistent, returns `Some((birth, death))` where
+/// `birth[i - 1]` is the proposed birth date and `death[i - 1]` is the proposed death date of person P_i.
+/// Returns `None` if the facts are not internally consistent.
+///
+/// # Complexity
+/// `O(m + n)` where `m = fact_form1.len() + fact_form2.len()`.
+pub fn proposed_dates(
+    fact_form1: &[(u32, u32)],
+    fact_form2: &[(u32, u32)],
+    n: usize,
+) -> Option<(Vec<usize>, Vec<usize>)> {
+    let (g, _) = build_graph(fact_form1, fact_form2, n);
+
     toposort(&g, None).ok().map(|topo| {
         let mut proposed_births = vec![0; n];
         let mut proposed_deaths = vec![0; n];
@@ -76,6 +112,492 @@ pub fn proposed_dates(
     })
 }
 
+/// Finds a set of facts that cannot be simultaneously satisfied.
+///
+/// When [`proposed_dates`] returns `None`, the facts contain a cycle: some set of
+/// facts forces an event to come before itself. Rebuilding the same constraint
+/// graph, this walks its strongly connected components (via `tarjan_scc`) to find
+/// one with more than one node, then reports every fact whose edge lies within
+/// that component. This is the "equivocation detection" idea of surfacing a
+/// contradictory set rather than a blank failure. The set is not minimised: a
+/// component can hold several cycles, and every fact with an edge inside it is
+/// reported, including ones that no single cycle needs.
+///
+/// # Return value
+/// Returns `None` if the facts are internally consistent (mirroring
+/// [`proposed_dates`]). Otherwise returns `Some(facts)`, the facts whose edges lie
+/// in one strongly connected component of the constraint graph (which contains at
+/// least one cycle). When there are multiple independent
+/// inconsistencies, only one is reported; fixing it and calling this function
+/// again will surface the next.
+pub fn conflicting_facts(
+    fact_form1: &[(u32, u32)],
+    fact_form2: &[(u32, u32)],
+    n: usize,
+) -> Option<Vec<Fact>> {
+    let (g, edge_facts) = build_graph(fact_form1, fact_form2, n);
+
+    if toposort(&g, None).is_ok() {
+        return None;
+    }
+
+    // `tarjan_scc` finds a strongly connected component with more than one node
+    // exactly when the graph has a cycle; toposort having already failed
+    // guarantees one exists.
+    let cycle: HashSet<_> = tarjan_scc(&g)
+        .into_iter()
+        .find(|scc| scc.len() > 1)
+        .expect("toposort failed but no cycle was found")
+        .into_iter()
+        .collect();
+
+    let facts = g
+        .edge_indices()
+        .filter(|&edge| {
+            let (src, dst) = g.edge_endpoints(edge).unwrap();
+            cycle.contains(&src) && cycle.contains(&dst)
+        })
+        .map(|edge| edge_facts[&edge])
+        .collect();
+
+    Some(facts)
+}
+
+/// `(earliest, latest)` date range, one entry per `B(i)` or per `D(i)`.
+type DateRanges = Vec<(usize, usize)>;
+
+/// Counts the nodes strictly reachable from `start` by following edges in `dir`
+/// (`Outgoing` for descendants, `Incoming` for ancestors). `start` itself is not
+/// counted.
+fn reachable_count(g: &DiGraph<Label, ()>, start: NodeIndex, dir: Direction) -> usize {
+    let mut visited = HashSet::new();
+    let mut stack = vec![start];
+    while let Some(x) = stack.pop() {
+        for next in g.neighbors_directed(x, dir) {
+            if visited.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    visited.len()
+}
+
+/// Produces, for every birth and death, the range of dates consistent with all facts.
+///
+/// [`proposed_dates`] hands out one arbitrary topological order, which hides how
+/// much freedom each date actually has. This instead computes, for every
+/// `B(i)`/`D(i)` node `x`, the earliest and latest rank `x` can take over *all*
+/// valid topological orders (not just one of them):
+///
+/// - `earliest[x]` is `1 + |ancestors(x)|`: `x` can never come before all of its
+///   own ancestors, and some valid order places it immediately after them.
+/// - `latest[x]` is `2n - |descendants(x)|`: symmetrically, `x` can never come
+///   after all of its own descendants.
+///
+/// Ancestor/descendant counts are reachability, not longest-path length — a node
+/// with several independent (unordered) predecessors still only has to wait for
+/// one of them to have happened, not for all of their combined chain lengths.
+///
+/// Nodes whose earliest equals latest are fully pinned by the facts; a wider
+/// range tells the researcher where more evidence is needed.
+///
+/// # Return value
+/// Returns `None` if the facts are not internally consistent (mirroring
+/// [`proposed_dates`]). Otherwise returns `Some((birth, death))` where
+/// `birth[i - 1]` and `death[i - 1]` are the `(earliest, latest)` range for
+/// person P_i's birth and death respectively.
+pub fn proposed_date_ranges(
+    fact_form1: &[(u32, u32)],
+    fact_form2: &[(u32, u32)],
+    n: usize,
+) -> Option<(DateRanges, DateRanges)> {
+    let (g, _) = build_graph(fact_form1, fact_form2, n);
+    if toposort(&g, None).is_err() {
+        return None;
+    }
+
+    let total = g.node_count();
+
+    let mut birth_ranges = vec![(0, 0); n];
+    let mut death_ranges = vec![(0, 0); n];
+    for node in g.node_indices() {
+        let ancestors = reachable_count(&g, node, Direction::Incoming);
+        let descendants = reachable_count(&g, node, Direction::Outgoing);
+        let range = (1 + ancestors, total - descendants);
+        match g[node] {
+            Label::Birth(i) => birth_ranges[i - 1] = range,
+            Label::Death(i) => death_ranges[i - 1] = range,
+        }
+    }
+
+    Some((birth_ranges, death_ranges))
+}
+
+/// Renders the constraint graph built from the given facts as Graphviz DOT.
+///
+/// Birth nodes are labeled `B1..Bn` and death nodes `D1..Dn`. The three edge
+/// classes are told apart by style so the rendered image is readable: a
+/// person's own lifespan is a solid black edge, a `fact_form1` entry ("died
+/// before born") is a dashed red edge, and a `fact_form2` entry ("overlapped")
+/// is a dotted blue edge. The result round-trips through [`from_dot`].
+pub fn to_dot(fact_form1: &[(u32, u32)], fact_form2: &[(u32, u32)], n: usize) -> String {
+    let (g, edge_facts) = build_graph(fact_form1, fact_form2, n);
+
+    let edge_attr = |_, edge: petgraph::graph::EdgeReference<'_, ()>| match edge_facts[&edge.id()]
+    {
+        Fact::Lifespan(_) => "color=black".to_string(),
+        Fact::DiedBeforeBorn(..) => "color=red, style=dashed".to_string(),
+        Fact::Overlap(..) => "color=blue, style=dotted".to_string(),
+    };
+    let node_attr = |_, (_, label): (NodeIndex, &Label)| {
+        let name = match label {
+            Label::Birth(i) => format!("B{i}"),
+            Label::Death(i) => format!("D{i}"),
+        };
+        format!("label=\"{name}\"")
+    };
+
+    // bound to locals rather than passed as inline temporaries: `Dot` borrows
+    // these closures, and the borrow must still be valid at the `format!` below
+    let dot = Dot::with_attr_getters(
+        &g,
+        &[Config::NodeNoLabel, Config::EdgeNoLabel],
+        &edge_attr,
+        &node_attr,
+    );
+
+    format!("{dot:?}")
+}
+
+/// The `fact_form1`, `fact_form2`, and `n` recovered by [`from_dot`].
+type ParsedFacts = (Vec<(u32, u32)>, Vec<(u32, u32)>, usize);
+
+/// Parses a DOT file produced by [`to_dot`] back into `fact_form1`, `fact_form2`, and `n`.
+///
+/// This relies on the node labeling and edge styling `to_dot` uses: node lines
+/// of the form `<index> [ label="B3" ]` recover which person and role each node
+/// index stands for, and edge lines are classified by the `dashed` (died
+/// before born) or `dotted` (overlap) style in their attributes; solid
+/// lifespan edges carry no fact of their own and are skipped.
+///
+/// # Return value
+/// Returns `Err` describing the problem if `dot` is not in the shape `to_dot`
+/// produces, rather than panicking; `dot` is arbitrary caller-provided input,
+/// not something this crate can assume is well-formed.
+pub fn from_dot(dot: &str) -> Result<ParsedFacts, String> {
+    fn person_number(label: &str) -> Result<u32, String> {
+        label
+            .get(1..)
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| format!("node label {label:?} is not of the form B<n> or D<n>"))
+    }
+
+    let mut labels: HashMap<usize, String> = HashMap::new();
+    let mut fact_form1 = Vec::new();
+    let mut overlaps: HashSet<(u32, u32)> = HashSet::new();
+    let mut n = 0;
+
+    for line in dot.lines() {
+        let line = line.trim();
+
+        if let Some(arrow) = line.find("->") {
+            let src: usize = line[..arrow]
+                .trim()
+                .parse()
+                .map_err(|_| format!("malformed edge source index in {line:?}"))?;
+            let rest = &line[arrow + 2..];
+            let open = rest
+                .find('[')
+                .ok_or_else(|| format!("edge line missing attributes: {line:?}"))?;
+            let dst: usize = rest[..open]
+                .trim()
+                .parse()
+                .map_err(|_| format!("malformed edge target index in {line:?}"))?;
+            let close = rest
+                .rfind(']')
+                .ok_or_else(|| format!("edge line missing attributes: {line:?}"))?;
+            let attrs = &rest[open + 1..close];
+
+            let src_label = labels
+                .get(&src)
+                .ok_or_else(|| format!("edge refers to undeclared node {src}"))?;
+            let dst_label = labels
+                .get(&dst)
+                .ok_or_else(|| format!("edge refers to undeclared node {dst}"))?;
+
+            if attrs.contains("dashed") {
+                fact_form1.push((person_number(src_label)?, person_number(dst_label)?));
+            } else if attrs.contains("dotted") {
+                let i = person_number(src_label)?;
+                let j = person_number(dst_label)?;
+                overlaps.insert((i.min(j), i.max(j)));
+            }
+        } else if let Some(open) = line.find('[') {
+            let idx: usize = line[..open]
+                .trim()
+                .parse()
+                .map_err(|_| format!("malformed node index in {line:?}"))?;
+            let close = line
+                .rfind(']')
+                .ok_or_else(|| format!("node line missing attributes: {line:?}"))?;
+            let label = line[open + 1..close]
+                .trim()
+                .trim_start_matches("label=")
+                .trim_matches('"')
+                .to_string();
+
+            n = n.max(person_number(&label)? as usize);
+            labels.insert(idx, label);
+        }
+    }
+
+    Ok((fact_form1, overlaps.into_iter().collect(), n))
+}
+
+/// Splits people into chronological chains with no overlapping lifespans.
+///
+/// A run is a sequence `P_i1, ..., P_ik` where each person provably died
+/// before the next was born, i.e. a strict chain of successive generations with
+/// no temporal overlap. Every person appears in exactly one run (possibly a run
+/// of length one, if they chain to nobody). Runs are built greedily and only
+/// extended forward, so a run is not necessarily the longest chain through its
+/// people: with `fact_form1 = [(2, 1)]` the runs are `[1]` and `[2]`, not `[2, 1]`.
+///
+/// This is a thin wrapper over [`collect_chronological_runs_filtered`] with a
+/// filter that accepts everyone.
+pub fn collect_chronological_runs(
+    fact_form1: &[(u32, u32)],
+    fact_form2: &[(u32, u32)],
+    n: usize,
+) -> Vec<Vec<usize>> {
+    collect_chronological_runs_filtered(fact_form1, fact_form2, n, |_| true)
+}
+
+/// Like [`collect_chronological_runs`], but restricted to a subpopulation.
+///
+/// Only people for which `filter` returns `true` are considered; everyone else
+/// appears in no run, and every `fact_form1` entry that mentions them is
+/// ignored. This mirrors rustworkx's filtered-run functions.
+///
+/// # Implementation
+/// This restricts the constraint graph to its `D(a) -> B(b)` ("died before
+/// born") edges, i.e. exactly the `fact_form1` facts, since those are the only
+/// edges that provably order one person's lifespan after another's. Scanning
+/// people in order, each unvisited person starts a new run that is then
+/// greedily extended: from the run's current person, follow an unvisited
+/// successor (preferring the lowest-numbered one) for as long as one exists.
+/// Marking every person visited as it is placed guarantees each lands in
+/// exactly one run; a run ends once its last person has no unvisited successor.
+pub fn collect_chronological_runs_filtered<F>(
+    fact_form1: &[(u32, u32)],
+    fact_form2: &[(u32, u32)],
+    n: usize,
+    filter: F,
+) -> Vec<Vec<usize>>
+where
+    F: Fn(usize) -> bool,
+{
+    let (_, edge_facts) = build_graph(fact_form1, fact_form2, n);
+
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); n + 1];
+    for fact in edge_facts.values() {
+        if let Fact::DiedBeforeBorn(a, b) = *fact {
+            let (a, b) = (a as usize, b as usize);
+            if filter(a) && filter(b) {
+                successors[a].push(b);
+            }
+        }
+    }
+    for adjacent in &mut successors {
+        adjacent.sort_unstable();
+    }
+
+    let mut visited = vec![false; n + 1];
+    let mut runs = Vec::new();
+
+    for start in 1..=n {
+        if !filter(start) || visited[start] {
+            continue;
+        }
+
+        let mut run = vec![start];
+        visited[start] = true;
+        let mut current = start;
+
+        while let Some(&next) = successors[current].iter().find(|&&p| !visited[p]) {
+            run.push(next);
+            visited[next] = true;
+            current = next;
+        }
+
+        runs.push(run);
+    }
+
+    runs
+}
+
+/// Maintains a valid ordering of proposed dates as facts arrive one at a time.
+///
+/// Rebuilding the constraint graph and re-running [`toposort`] on every new fact is
+/// wasteful when an ethnographer is adding evidence incrementally. `Ethnographer`
+/// instead keeps a position array `ord` (a node's place in some valid topological
+/// order) and updates it online using the Pearce-Kelly algorithm: inserting an
+/// edge `(u, v)` only disturbs the order when `ord[u] >= ord[v]`, and even then
+/// only nodes between `v` and `u` in the existing order need to move.
+pub struct Ethnographer {
+    g: DiGraph<Label, ()>,
+    /// `ord[node.index()]` is that node's position in the current valid topological order.
+    ord: Vec<usize>,
+    birth_nodes: Vec<NodeIndex>,
+    death_nodes: Vec<NodeIndex>,
+    n: usize,
+}
+
+impl Ethnographer {
+    /// Creates an `Ethnographer` for `n` people with no facts beyond each
+    /// person's own lifespan (`B(i)` before `D(i)`).
+    pub fn new(n: usize) -> Self {
+        let mut g = DiGraph::new();
+
+        let birth_nodes: Vec<_> = (1..=n).map(|i| g.add_node(Label::Birth(i))).collect();
+        let death_nodes: Vec<_> = (1..=n).map(|i| g.add_node(Label::Death(i))).collect();
+
+        let mut ord = vec![0; 2 * n];
+        for (pos, node) in birth_nodes.iter().chain(death_nodes.iter()).enumerate() {
+            ord[node.index()] = pos;
+        }
+
+        for i in 0..n {
+            g.add_edge(birth_nodes[i], death_nodes[i], ());
+        }
+
+        Ethnographer {
+            g,
+            ord,
+            birth_nodes,
+            death_nodes,
+            n,
+        }
+    }
+
+    /// Records that P_a died before P_b was born.
+    ///
+    /// Returns `false` without changing anything if the fact contradicts facts
+    /// already recorded.
+    pub fn add_fact_form1(&mut self, a: usize, b: usize) -> bool {
+        self.insert_edge(self.death_nodes[a - 1], self.birth_nodes[b - 1])
+    }
+
+    /// Records that P_a and P_b's lifespans overlapped at least partially.
+    ///
+    /// Returns `false` without changing anything if the fact contradicts facts
+    /// already recorded.
+    pub fn add_fact_form2(&mut self, a: usize, b: usize) -> bool {
+        if !self.insert_edge(self.birth_nodes[a - 1], self.death_nodes[b - 1]) {
+            return false;
+        }
+
+        if !self.insert_edge(self.birth_nodes[b - 1], self.death_nodes[a - 1]) {
+            // the overlap is a single fact; don't leave half of it recorded
+            let edge = self
+                .g
+                .find_edge(self.birth_nodes[a - 1], self.death_nodes[b - 1])
+                .expect("edge was just inserted");
+            self.g.remove_edge(edge);
+            return false;
+        }
+
+        true
+    }
+
+    /// Produces proposed dates of birth and death consistent with every fact
+    /// recorded so far, in the same format as [`proposed_dates`].
+    pub fn proposed_dates(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut nodes: Vec<NodeIndex> = (0..self.g.node_count()).map(NodeIndex::new).collect();
+        nodes.sort_by_key(|&node| self.ord[node.index()]);
+
+        let mut proposed_births = vec![0; self.n];
+        let mut proposed_deaths = vec![0; self.n];
+
+        for (date, &node) in nodes.iter().enumerate() {
+            match self.g[node] {
+                Label::Birth(i) => proposed_births[i - 1] = date + 1,
+                Label::Death(i) => proposed_deaths[i - 1] = date + 1,
+            }
+        }
+
+        (proposed_births, proposed_deaths)
+    }
+
+    /// Inserts edge `(u, v)`, restoring a valid topological order in `self.ord`.
+    ///
+    /// If `ord[u] < ord[v]` the existing order already satisfies the new edge and
+    /// nothing further is needed. Otherwise this runs a forward DFS from `v`
+    /// bounded to nodes ordered before `u` (`delta_f`) and a backward DFS from `u`
+    /// bounded to nodes ordered after `v` (`delta_b`). If the forward DFS would
+    /// step onto `u`, the edge closes a cycle and is rejected. Otherwise the
+    /// pooled `ord` slots of `delta_b` and `delta_f` are reassigned so every
+    /// `delta_b` node precedes every `delta_f` node, each group keeping its
+    /// existing relative order.
+    fn insert_edge(&mut self, u: NodeIndex, v: NodeIndex) -> bool {
+        let edge = self.g.add_edge(u, v, ());
+
+        if self.ord[u.index()] < self.ord[v.index()] {
+            return true;
+        }
+
+        let ub = self.ord[u.index()];
+        let lb = self.ord[v.index()];
+
+        let mut delta_f = Vec::new();
+        let mut seen_f = HashSet::new();
+        seen_f.insert(v);
+        let mut stack = vec![v];
+        while let Some(x) = stack.pop() {
+            delta_f.push(x);
+            for succ in self.g.neighbors(x) {
+                if succ == u {
+                    // the new edge closed a cycle; reject it
+                    self.g.remove_edge(edge);
+                    return false;
+                }
+                if self.ord[succ.index()] < ub && seen_f.insert(succ) {
+                    stack.push(succ);
+                }
+            }
+        }
+
+        let mut delta_b = Vec::new();
+        let mut seen_b = HashSet::new();
+        seen_b.insert(u);
+        let mut stack = vec![u];
+        while let Some(x) = stack.pop() {
+            delta_b.push(x);
+            for pred in self.g.neighbors_directed(x, Direction::Incoming) {
+                if self.ord[pred.index()] > lb && seen_b.insert(pred) {
+                    stack.push(pred);
+                }
+            }
+        }
+
+        delta_b.sort_by_key(|&node| self.ord[node.index()]);
+        delta_f.sort_by_key(|&node| self.ord[node.index()]);
+
+        let mut slots: Vec<usize> = delta_b
+            .iter()
+            .chain(delta_f.iter())
+            .map(|&node| self.ord[node.index()])
+            .collect();
+        slots.sort_unstable();
+
+        for (node, slot) in delta_b.into_iter().chain(delta_f).zip(slots) {
+            self.ord[node.index()] = slot;
+        }
+
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +628,220 @@ mod tests {
             panic!("proposed dates not internally consistent");
         }
     }
+
+    #[test]
+    fn conflicting_facts_is_none_when_consistent() {
+        let fact_form1 = vec![(1, 2), (3, 4), (4, 6)];
+        let fact_form2 = vec![(2, 3), (5, 6)];
+        let n = 6;
+
+        assert!(conflicting_facts(&fact_form1, &fact_form2, n).is_none());
+    }
+
+    #[test]
+    fn conflicting_facts_reports_the_cycle() {
+        // P1 died before P2 was born, P2 died before P3 was born, P3 died before P1 was born:
+        // a direct contradiction, since that forces P1 to have died before itself was born.
+        let fact_form1 = vec![(1, 2), (2, 3), (3, 1)];
+        let fact_form2 = vec![];
+        let n = 3;
+
+        assert!(proposed_dates(&fact_form1, &fact_form2, n).is_none());
+
+        let facts = conflicting_facts(&fact_form1, &fact_form2, n)
+            .expect("facts are inconsistent and should report a cycle");
+
+        assert!(facts.contains(&Fact::DiedBeforeBorn(1, 2)));
+        assert!(facts.contains(&Fact::DiedBeforeBorn(2, 3)));
+        assert!(facts.contains(&Fact::DiedBeforeBorn(3, 1)));
+    }
+
+    #[test]
+    fn ethnographer_matches_proposed_dates_when_facts_arrive_incrementally() {
+        let fact_form1 = [(1, 2), (3, 4), (4, 6)];
+        let fact_form2 = [(2, 3), (5, 6)];
+        let n = 6;
+
+        let mut ethnographer = Ethnographer::new(n);
+        for &(a, b) in &fact_form1 {
+            assert!(ethnographer.add_fact_form1(a as usize, b as usize));
+        }
+        for &(a, b) in &fact_form2 {
+            assert!(ethnographer.add_fact_form2(a as usize, b as usize));
+        }
+
+        let (birth, death) = ethnographer.proposed_dates();
+
+        for (a, b) in &fact_form1 {
+            assert!(death[(a - 1) as usize] < birth[(b - 1) as usize]);
+        }
+        for (a, b) in &fact_form2 {
+            assert!(
+                birth[(a - 1) as usize] < death[(b - 1) as usize]
+                    && birth[(b - 1) as usize] < death[(a - 1) as usize]
+            );
+        }
+        for i in 1..=n {
+            assert!(birth[i - 1] < death[i - 1]);
+        }
+    }
+
+    #[test]
+    fn ethnographer_rejects_facts_that_close_a_cycle() {
+        let mut ethnographer = Ethnographer::new(3);
+
+        assert!(ethnographer.add_fact_form1(1, 2));
+        assert!(ethnographer.add_fact_form1(2, 3));
+        // P3 died before P1 was born would force P1 to die before itself was born
+        assert!(!ethnographer.add_fact_form1(3, 1));
+
+        // the rejected fact left no trace: the facts recorded before it still hold
+        let (birth, death) = ethnographer.proposed_dates();
+        assert!(death[0] < birth[1]);
+        assert!(death[1] < birth[2]);
+    }
+
+    #[test]
+    fn proposed_date_ranges_is_none_when_inconsistent() {
+        let fact_form1 = vec![(1, 2), (2, 1)];
+        let fact_form2 = vec![];
+
+        assert!(proposed_date_ranges(&fact_form1, &fact_form2, 2).is_none());
+    }
+
+    #[test]
+    fn proposed_date_ranges_pins_a_fully_constrained_chain() {
+        // a strict chain P1 died before P2 born, P2 died before P3 born leaves
+        // no freedom: every date is forced to a single value
+        let fact_form1 = vec![(1, 2), (2, 3)];
+        let fact_form2 = vec![];
+        let n = 3;
+
+        let (birth, death) =
+            proposed_date_ranges(&fact_form1, &fact_form2, n).expect("facts are consistent");
+
+        for i in 0..n {
+            assert_eq!(birth[i].0, birth[i].1, "P{} birth should be pinned", i + 1);
+            assert_eq!(death[i].0, death[i].1, "P{} death should be pinned", i + 1);
+        }
+        assert_eq!(birth[0], (1, 1));
+        assert_eq!(death[2], (6, 6));
+    }
+
+    #[test]
+    fn proposed_date_ranges_leaves_freedom_for_unconstrained_people() {
+        // P1 and P2 have no facts relating them to each other at all, so either
+        // could come entirely before the other: e.g. B1,D1,B2,D2 or B2,D2,B1,D1
+        // are both valid total orders, and P1's birth/death range must cover both.
+        let fact_form1 = vec![];
+        let fact_form2 = vec![];
+        let n = 2;
+
+        let (birth, death) =
+            proposed_date_ranges(&fact_form1, &fact_form2, n).expect("facts are consistent");
+
+        assert_eq!(birth[0], (1, 3));
+        assert_eq!(birth[1], (1, 3));
+        assert_eq!(death[0], (2, 4));
+        assert_eq!(death[1], (2, 4));
+    }
+
+    #[test]
+    fn to_dot_labels_every_node_and_styles_every_edge_class() {
+        let fact_form1 = vec![(1, 2)];
+        let fact_form2 = vec![(2, 3)];
+        let n = 3;
+
+        let dot = to_dot(&fact_form1, &fact_form2, n);
+
+        for label in ["B1", "B2", "B3", "D1", "D2", "D3"] {
+            assert!(dot.contains(label), "missing node label {label}");
+        }
+        assert!(dot.contains("color=black"));
+        assert!(dot.contains("dashed"));
+        assert!(dot.contains("dotted"));
+    }
+
+    #[test]
+    fn to_dot_and_from_dot_round_trip() {
+        let fact_form1 = vec![(1, 2), (3, 4), (4, 6)];
+        let fact_form2 = vec![(2, 3), (5, 6)];
+        let n = 6;
+
+        let dot = to_dot(&fact_form1, &fact_form2, n);
+        let (parsed_form1, parsed_form2, parsed_n) =
+            from_dot(&dot).expect("to_dot's own output should parse");
+
+        assert_eq!(parsed_n, n);
+
+        let mut expected_form1 = fact_form1.clone();
+        expected_form1.sort_unstable();
+        let mut actual_form1 = parsed_form1;
+        actual_form1.sort_unstable();
+        assert_eq!(actual_form1, expected_form1);
+
+        let mut expected_form2: Vec<_> = fact_form2
+            .iter()
+            .map(|&(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        expected_form2.sort_unstable();
+        let mut actual_form2 = parsed_form2;
+        actual_form2.sort_unstable();
+        assert_eq!(actual_form2, expected_form2);
+
+        // the round-tripped facts propose the same dates as the originals
+        assert_eq!(
+            proposed_dates(&fact_form1, &fact_form2, n),
+            proposed_dates(&actual_form1, &actual_form2, parsed_n)
+        );
+    }
+
+    #[test]
+    fn from_dot_reports_an_error_instead_of_panicking_on_malformed_input() {
+        // edge source is not a node index at all
+        assert!(from_dot("x -> y [ style=dashed ]").is_err());
+        // edge refers to a node that was never declared
+        assert!(from_dot("0 [ label=\"B1\" ]\n0 -> 1 [ style=dashed ]").is_err());
+    }
+
+    #[test]
+    fn collect_chronological_runs_chains_successive_generations() {
+        // P1 died before P2 was born, P2 died before P3 was born: one run of
+        // three. P4 and P5 are unrelated to everyone, so each is its own run.
+        let fact_form1 = vec![(1, 2), (2, 3)];
+        let fact_form2 = vec![];
+        let n = 5;
+
+        let mut runs = collect_chronological_runs(&fact_form1, &fact_form2, n);
+        runs.sort();
+
+        assert_eq!(runs, vec![vec![1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn collect_chronological_runs_every_person_appears_exactly_once() {
+        let fact_form1 = vec![(1, 2), (3, 4), (4, 6)];
+        let fact_form2 = vec![(2, 3), (5, 6)];
+        let n = 6;
+
+        let runs = collect_chronological_runs(&fact_form1, &fact_form2, n);
+
+        let mut seen: Vec<usize> = runs.into_iter().flatten().collect();
+        seen.sort_unstable();
+        assert_eq!(seen, (1..=n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn collect_chronological_runs_filtered_excludes_non_matching_people() {
+        let fact_form1 = vec![(1, 2), (2, 3)];
+        let fact_form2 = vec![];
+        let n = 3;
+
+        // excluding P2 breaks the chain into two singleton runs
+        let mut runs =
+            collect_chronological_runs_filtered(&fact_form1, &fact_form2, n, |p| p != 2);
+        runs.sort();
+
+        assert_eq!(runs, vec![vec![1], vec![3]]);
+    }
 }